@@ -1,14 +1,208 @@
 #[macro_use]
 extern crate serde;
 use candid::{Decode, Encode};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 use ic_cdk::api::time;
+use ic_cdk_timers::TimerId;
+use sha2::{Digest, Sha256};
 use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
 use ic_stable_structures::{BoundedStorable, Cell, DefaultMemoryImpl, StableBTreeMap, Storable};
+use std::time::Duration;
 use std::{borrow::Cow, cell::RefCell};
 
 type Memory = VirtualMemory<DefaultMemoryImpl>;
 type IdCell = Cell<u64, Memory>;
 
+// Storage Abstraction
+
+/// A minimal key/value persistence interface shared by every handler.
+///
+/// Decoupling the business logic from `StableBTreeMap` lets the core
+/// functions be exercised against an in-memory map in unit tests and lets a
+/// future version swap in a different stable backend without touching the
+/// handlers. The `iter` method materializes the pairs into a `Vec` so the
+/// trait stays object-safe-ish and identical across both backends, which do
+/// not share an iterator type.
+trait Repository<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+    fn get(&self, key: &K) -> Option<V>;
+    fn remove(&mut self, key: &K) -> Option<V>;
+    fn iter(&self) -> Vec<(K, V)>;
+    fn contains_key(&self, key: &K) -> bool;
+}
+
+impl<K, V, M> Repository<K, V> for StableBTreeMap<K, V, M>
+where
+    K: Storable + BoundedStorable + Ord + Clone,
+    V: Storable + BoundedStorable,
+    M: ic_stable_structures::Memory,
+{
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        StableBTreeMap::insert(self, key, value)
+    }
+
+    fn get(&self, key: &K) -> Option<V> {
+        StableBTreeMap::get(self, key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        StableBTreeMap::remove(self, key)
+    }
+
+    fn iter(&self) -> Vec<(K, V)> {
+        StableBTreeMap::iter(self).collect()
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        StableBTreeMap::contains_key(self, key)
+    }
+}
+
+impl<K, V> Repository<K, V> for std::collections::BTreeMap<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        std::collections::BTreeMap::insert(self, key, value)
+    }
+
+    fn get(&self, key: &K) -> Option<V> {
+        std::collections::BTreeMap::get(self, key).cloned()
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        std::collections::BTreeMap::remove(self, key)
+    }
+
+    fn iter(&self) -> Vec<(K, V)> {
+        std::collections::BTreeMap::iter(self)
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        std::collections::BTreeMap::contains_key(self, key)
+    }
+}
+
+/// A zero-byte marker value for posting-list indexes, where only the
+/// presence of a key (not an associated value) carries information.
+#[derive(candid::CandidType, Serialize, Deserialize, Clone, Default)]
+struct Present;
+
+impl Storable for Present {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Borrowed(&[])
+    }
+
+    fn from_bytes(_bytes: Cow<[u8]>) -> Self {
+        Present
+    }
+}
+
+impl BoundedStorable for Present {
+    const MAX_SIZE: u32 = 0;
+    const IS_FIXED_SIZE: bool = true;
+}
+
+/// One posting in a secondary index: a single stable-map entry per
+/// `(index_key, id)` pair, ordered by `index_key` then `id`.
+///
+/// Storing one posting per entry means a key can back an arbitrary number of
+/// ids, and a page of them is read with a stable-map range scan.
+#[derive(candid::CandidType, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct IndexEntry<K> {
+    index_key: K,
+    id: u64,
+}
+
+impl<K> Storable for IndexEntry<K>
+where
+    K: candid::CandidType + for<'de> Deserialize<'de> + Clone,
+{
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("Encoding failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).expect("Decoding failed")
+    }
+}
+
+impl<K> BoundedStorable for IndexEntry<K>
+where
+    K: candid::CandidType + for<'de> Deserialize<'de> + Clone,
+{
+    // A `String` owner/status key (or a `u64` property id) plus the posted id
+    // and candid framing.
+    const MAX_SIZE: u32 = 512;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+/// Adds a posting for `id` under `key`, so it is returned by later
+/// `posting_page` reads over `key`.
+fn index_append<K, R>(index: &mut R, key: K, id: u64)
+where
+    K: Ord + Clone,
+    R: Repository<IndexEntry<K>, Present>,
+{
+    index.insert(IndexEntry { index_key: key, id }, Present);
+}
+
+/// Removes the posting for `id` under `key`, used when a record moves
+/// between values of an indexed field (e.g. maintenance status).
+fn index_remove<K, R>(index: &mut R, key: K, id: u64)
+where
+    K: Ord + Clone,
+    R: Repository<IndexEntry<K>, Present>,
+{
+    index.remove(&IndexEntry { index_key: key, id });
+}
+
+/// Returns a page of ids posted under `key`, strictly after `start_after`
+/// and capped at `limit`.
+///
+/// Modeled on S3 list pagination: `start_after` is an exclusive cursor and an
+/// empty/`None` cursor starts at the beginning. Backed by a range scan from
+/// `(key, start_after + 1)` to `(key, u64::MAX)`, so the page is unaffected
+/// by how many ids `key` has posted in total.
+fn posting_page<K>(
+    index: &StableBTreeMap<IndexEntry<K>, Present, Memory>,
+    key: K,
+    start_after: Option<u64>,
+    limit: u32,
+) -> Vec<u64>
+where
+    K: candid::CandidType + for<'de> Deserialize<'de> + Clone + Ord,
+{
+    let lower = IndexEntry {
+        index_key: key.clone(),
+        id: start_after.map_or(0, |cursor| cursor.saturating_add(1)),
+    };
+    let upper = IndexEntry {
+        index_key: key,
+        id: u64::MAX,
+    };
+    index
+        .range(lower..=upper)
+        .take(limit as usize)
+        .map(|(entry, _)| entry.id)
+        .collect()
+}
+
+/// Reserves and returns the next monotonically increasing identifier.
+fn next_id() -> u64 {
+    ID_COUNTER.with(|counter| {
+        let current_value = *counter.borrow().get();
+        counter
+            .borrow_mut()
+            .set(current_value + 1)
+            .expect("Failed to increment ID counter");
+        current_value
+    })
+}
+
 // Data Structures
 
 #[derive(candid::CandidType, Serialize, Deserialize, Clone, Default)]
@@ -43,10 +237,13 @@ struct LeaseAgreement {
     start_date: u64,
     end_date: u64,
     created_at: u64,
-    digital_signature: String, // Added field for digital signature
+    digital_signature: String, // Hex-encoded Ed25519 signature over the lease terms
+    public_key: Vec<u8>,       // Tenant's Ed25519 public key, verified at creation
+    status: String,            // Lifecycle status, transitioned automatically on expiry
 }
 
 impl LeaseAgreement {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         id: u64,
         property_id: u64,
@@ -54,7 +251,8 @@ impl LeaseAgreement {
         rent: f64,
         start_date: u64,
         end_date: u64,
-        digital_signature: String, // Added parameter for digital signature
+        digital_signature: String, // Hex-encoded Ed25519 signature
+        public_key: Vec<u8>,       // Verified tenant public key
     ) -> Self {
         Self {
             id,
@@ -65,6 +263,8 @@ impl LeaseAgreement {
             end_date,
             created_at: time() / 1_000_000_000, // Convert nanoseconds to seconds
             digital_signature, // Initialize digital signature
+            public_key,        // Store the verified public key alongside the agreement
+            status: "active".to_string(), // New leases start active until they expire
         }
     }
 }
@@ -118,8 +318,300 @@ thread_local! {
         RefCell::new(StableBTreeMap::init(
             MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(3)))
     ));
+
+    // Secondary indexes, maintained on every mutation for O(log n) lookups.
+    // One stable-map entry per posting (see `IndexEntry`), so a key's list of
+    // ids has no upper bound on size.
+    static OWNER_PROPERTY_INDEX: RefCell<StableBTreeMap<IndexEntry<String>, Present, Memory>> =
+        RefCell::new(StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(4)))
+    ));
+
+    static PROPERTY_LEASE_INDEX: RefCell<StableBTreeMap<IndexEntry<u64>, Present, Memory>> =
+        RefCell::new(StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(5)))
+    ));
+
+    static MAINTENANCE_STATUS_INDEX: RefCell<StableBTreeMap<IndexEntry<String>, Present, Memory>> =
+        RefCell::new(StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(6)))
+    ));
+
+    // Last timestamp handed out, so operation timestamps stay strictly increasing.
+    static LAST_TIMESTAMP: RefCell<IdCell> = RefCell::new(
+        IdCell::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(7))), 0)
+            .expect("Cannot create the timestamp cell")
+    );
+
+    // Append-only log of every mutating operation, keyed by its timestamp.
+    static OPERATION_LOG: RefCell<StableBTreeMap<u64, Operation, Memory>> =
+        RefCell::new(StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(8)))
+    ));
+
+    // Latest materialized-state checkpoint, tagged with its last applied timestamp.
+    static CHECKPOINT: RefCell<Cell<StateSnapshot, Memory>> = RefCell::new(
+        Cell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(9))),
+            StateSnapshot::default(),
+        ).expect("Cannot create the checkpoint cell")
+    );
+
+    // Count of operations appended since the last checkpoint.
+    static OPERATION_COUNTER: RefCell<IdCell> = RefCell::new(
+        IdCell::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(10))), 0)
+            .expect("Cannot create the operation counter")
+    );
+
+    // Per-document metadata for chunked attachments.
+    static BLOB_META: RefCell<StableBTreeMap<BlobRef, BlobMeta, Memory>> =
+        RefCell::new(StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(11)))
+    ));
+
+    // Ordered document chunks, one chunk per entry.
+    static BLOB_CHUNKS: RefCell<StableBTreeMap<BlobChunkKey, Chunk, Memory>> =
+        RefCell::new(StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(12)))
+    ));
+
+    // Configuration for the lifecycle timer (scan interval and expiry grace).
+    static LIFECYCLE_POLICY: RefCell<Cell<LifecyclePolicy, Memory>> = RefCell::new(
+        Cell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(13))),
+            LifecyclePolicy::default(),
+        ).expect("Cannot create the lifecycle policy cell")
+    );
+
+    // Last rent cycle a rent-due entry was emitted for, keyed by lease id.
+    static LAST_RENT_CYCLE: RefCell<StableBTreeMap<u64, u64, Memory>> =
+        RefCell::new(StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(14)))
+    ));
+
+    // Handle of the currently registered lifecycle timer, if any. Kept in heap
+    // memory since `TimerId`s do not survive upgrades and are re-registered.
+    static LIFECYCLE_TIMER: RefCell<Option<TimerId>> = RefCell::new(None);
+}
+
+/// Returns a unique, strictly increasing timestamp for the next operation.
+///
+/// Uses wall-clock seconds when they advance and otherwise bumps the previous
+/// value by one, so several operations within the same block (where `time()`
+/// does not move) still receive distinct, ordered timestamps.
+fn next_timestamp() -> u64 {
+    LAST_TIMESTAMP.with(|cell| {
+        let previous = *cell.borrow().get();
+        let now = time() / 1_000_000_000; // Convert nanoseconds to seconds
+        let timestamp = if now > previous { now } else { previous + 1 };
+        cell.borrow_mut()
+            .set(timestamp)
+            .expect("Failed to advance the timestamp cell");
+        timestamp
+    })
+}
+
+/// Appends an operation to the log and refreshes the materialized-state
+/// checkpoint every `KEEP_STATE_EVERY` operations so recent-state queries don't
+/// need to replay from genesis. The checkpoint is a read-path cache only: it
+/// never causes operations to be dropped from the log, which is the
+/// canister's actual audit trail.
+fn append_operation(op: Operation) {
+    let timestamp = next_timestamp();
+    OPERATION_LOG.with(|log| log.borrow_mut().insert(timestamp, op));
+
+    let count = OPERATION_COUNTER.with(|counter| {
+        let next = *counter.borrow().get() + 1;
+        counter
+            .borrow_mut()
+            .set(next)
+            .expect("Failed to increment operation counter");
+        next
+    });
+
+    if count % KEEP_STATE_EVERY == 0 {
+        checkpoint_state();
+    }
+}
+
+/// Serializes the current materialized state into the checkpoint cell so
+/// `reconstruct_state` can skip straight to recent history instead of
+/// replaying every operation since genesis.
+///
+/// This does not touch `OPERATION_LOG`: the log is the canister's audit trail
+/// and is never pruned, so `get_property_history` and `get_state_at` see every
+/// operation back to genesis. The checkpoint only speeds up reads of recent
+/// state.
+fn checkpoint_state() {
+    let properties = PROPERTIES_STORAGE
+        .with(|storage| storage.borrow().iter().map(|(_, p)| p).collect::<Vec<_>>());
+    let leases =
+        LEASES_STORAGE.with(|storage| storage.borrow().iter().map(|(_, l)| l).collect::<Vec<_>>());
+    let maintenance = MAINTENANCE_STORAGE
+        .with(|storage| storage.borrow().iter().map(|(_, r)| r).collect::<Vec<_>>());
+    let last_timestamp = LAST_TIMESTAMP.with(|cell| *cell.borrow().get());
+
+    let snapshot = StateSnapshot {
+        last_timestamp,
+        properties,
+        leases,
+        maintenance,
+    };
+    CHECKPOINT.with(|cell| {
+        cell.borrow_mut()
+            .set(snapshot)
+            .expect("Failed to write checkpoint")
+    });
+}
+
+/// Applies a single operation to an in-memory state map set, used to replay the
+/// log on top of a checkpoint.
+fn apply_operation(op: &Operation, state: &mut StateSnapshot) {
+    match op {
+        Operation::CreateProperty(p) => state.properties.push(p.clone()),
+        Operation::UpdateValuation {
+            property_id,
+            valuation,
+        } => {
+            if let Some(p) = state.properties.iter_mut().find(|p| p.id == *property_id) {
+                p.valuation = *valuation;
+            }
+        }
+        Operation::CreateLease(l) => state.leases.push(l.clone()),
+        Operation::CreateMaintenance(r) => state.maintenance.push(r.clone()),
+        Operation::ChangeMaintenanceStatus { request_id, status } => {
+            if let Some(r) = state.maintenance.iter_mut().find(|r| r.id == *request_id) {
+                r.status = status.clone();
+            }
+        }
+        Operation::ChangeLeaseStatus { lease_id, status } => {
+            if let Some(l) = state.leases.iter_mut().find(|l| l.id == *lease_id) {
+                l.status = status.clone();
+            }
+        }
+        Operation::ChangePropertyStatus {
+            property_id,
+            status,
+        } => {
+            if let Some(p) = state.properties.iter_mut().find(|p| p.id == *property_id) {
+                p.status = status.clone();
+            }
+        }
+    }
+}
+
+/// Reconstructs the materialized state as of `timestamp` by loading the latest
+/// checkpoint at or before it — or replaying from genesis if the checkpoint
+/// postdates `timestamp` — and replaying operations up to `timestamp`. Since
+/// the operation log is never pruned, this can always reconstruct any point
+/// back to genesis.
+fn reconstruct_state(timestamp: u64) -> StateSnapshot {
+    let mut state = CHECKPOINT.with(|cell| cell.borrow().get().clone());
+    if state.last_timestamp > timestamp {
+        // The checkpoint is newer than the requested point; replay from scratch
+        // over whatever operations remain.
+        state = StateSnapshot::default();
+    }
+    let floor = state.last_timestamp;
+    let ops = OPERATION_LOG.with(|log| {
+        log.borrow()
+            .iter()
+            .filter(|(ts, _)| *ts > floor && *ts <= timestamp)
+            .map(|(_, op)| op)
+            .collect::<Vec<_>>()
+    });
+    for op in &ops {
+        apply_operation(op, &mut state);
+    }
+    state.last_timestamp = timestamp;
+    state
+}
+
+// Operation Log
+
+/// Number of operations between materialized-state checkpoints.
+const KEEP_STATE_EVERY: u64 = 64;
+
+/// A single mutating event appended to the operation log.
+///
+/// The materialized `Property`/`LeaseAgreement`/`MaintenanceRequest` maps are a
+/// cache that can be rebuilt by replaying these operations in timestamp order,
+/// which gives the platform the auditable history that in-place writes lack.
+#[derive(candid::CandidType, Serialize, Deserialize, Clone)]
+enum Operation {
+    CreateProperty(Property),
+    UpdateValuation { property_id: u64, valuation: f64 },
+    CreateLease(LeaseAgreement),
+    CreateMaintenance(MaintenanceRequest),
+    ChangeMaintenanceStatus { request_id: u64, status: String },
+    ChangeLeaseStatus { lease_id: u64, status: String },
+    ChangePropertyStatus { property_id: u64, status: String },
+}
+
+impl Operation {
+    /// The property id an operation concerns, if any, used by history queries.
+    fn property_id(&self) -> Option<u64> {
+        match self {
+            Operation::CreateProperty(p) => Some(p.id),
+            Operation::UpdateValuation { property_id, .. } => Some(*property_id),
+            Operation::CreateLease(l) => Some(l.property_id),
+            Operation::CreateMaintenance(r) => Some(r.property_id),
+            Operation::ChangeMaintenanceStatus { .. } => None,
+            Operation::ChangeLeaseStatus { .. } => None,
+            Operation::ChangePropertyStatus { property_id, .. } => Some(*property_id),
+        }
+    }
+}
+
+/// The materialized state reconstructed from a checkpoint plus replayed
+/// operations, also used as the checkpoint payload.
+#[derive(candid::CandidType, Serialize, Deserialize, Clone, Default)]
+struct StateSnapshot {
+    last_timestamp: u64,
+    properties: Vec<Property>,
+    leases: Vec<LeaseAgreement>,
+    maintenance: Vec<MaintenanceRequest>,
+}
+
+// Blob Store
+
+/// Fixed chunk size; each stored chunk holds at most this many bytes so a
+/// single map entry always stays within `BoundedStorable::MAX_SIZE`.
+const CHUNK_SIZE: u64 = 1024 * 1024; // 1 MiB
+
+/// Identifies an attachment owned by a property or lease.
+///
+/// `owner_kind` is a small tag such as `"property"` or `"lease"`, `owner_id`
+/// is the owning record's id, and `key` is a caller-chosen document name.
+#[derive(candid::CandidType, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct BlobRef {
+    owner_kind: String,
+    owner_id: u64,
+    key: String,
+}
+
+/// Key for a single ordered chunk of a document.
+#[derive(candid::CandidType, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct BlobChunkKey {
+    owner_kind: String,
+    owner_id: u64,
+    key: String,
+    index: u32,
 }
 
+/// Per-document metadata tracking how many chunks have been written and whether
+/// the upload has been finalized against a verified content hash.
+#[derive(candid::CandidType, Serialize, Deserialize, Clone, Default)]
+struct BlobMeta {
+    chunk_count: u32,
+    sha256: Vec<u8>,
+    finalized: bool,
+}
+
+/// A single ordered chunk of a document's bytes.
+#[derive(candid::CandidType, Serialize, Deserialize, Clone, Default)]
+struct Chunk(Vec<u8>);
+
 // Payload Definitions
 
 #[derive(candid::CandidType, Deserialize, Serialize)]
@@ -137,7 +629,9 @@ struct LeaseAgreementPayload {
     rent: f64,
     start_date: u64,
     end_date: u64,
-    digital_signature: String, // Added field for digital signature
+    digital_signature: String, // Hex-encoded Ed25519 signature over `message`
+    public_key: Vec<u8>,       // Tenant's Ed25519 public key (32 bytes)
+    message: Vec<u8>,          // The signed bytes, checked against the canonical terms
 }
 
 #[derive(candid::CandidType, Deserialize, Serialize)]
@@ -161,18 +655,48 @@ struct MaintenanceRequestPayload {
 /// * `Result<Property, String>` - The created property or an error message.
 #[ic_cdk::update]
 fn create_property(payload: PropertyPayload) -> Result<Property, String> {
+    let id = next_id();
+    let property = PROPERTIES_STORAGE
+        .with(|storage| create_property_in(&mut *storage.borrow_mut(), id, payload))?;
+    OWNER_PROPERTY_INDEX
+        .with(|index| index_append(&mut *index.borrow_mut(), property.owner.clone(), property.id));
+    append_operation(Operation::CreateProperty(property.clone()));
+    Ok(property)
+}
+
+/// Updates a property's valuation, recording the change as an operation so the
+/// valuation history is auditable.
+#[ic_cdk::update]
+fn update_property_valuation(id: u64, valuation: f64) -> Result<Property, String> {
+    let property = PROPERTIES_STORAGE.with(|storage| {
+        let mut storage = storage.borrow_mut();
+        match storage.get(&id) {
+            Some(mut property) => {
+                property.valuation = valuation;
+                storage.insert(id, property.clone());
+                Ok(property)
+            }
+            None => Err("Property not found".to_string()),
+        }
+    })?;
+    append_operation(Operation::UpdateValuation {
+        property_id: id,
+        valuation,
+    });
+    Ok(property)
+}
+
+/// Core property-creation logic, parametrized over the repository so it can be
+/// driven by either the stable backend or an in-memory map.
+fn create_property_in<R: Repository<u64, Property>>(
+    repo: &mut R,
+    id: u64,
+    payload: PropertyPayload,
+) -> Result<Property, String> {
     // Validate the payload to ensure all fields are provided
     if payload.address.is_empty() || payload.owner.is_empty() {
         return Err("Address and owner are required".to_string());
     }
-    let id = ID_COUNTER.with(|counter| {
-        let current_value = *counter.borrow().get();
-        counter
-            .borrow_mut()
-            .set(current_value + 1)
-            .expect("Failed to increment ID counter");
-        current_value
-    });
     let property = Property::new(
         id,
         payload.address,
@@ -180,7 +704,7 @@ fn create_property(payload: PropertyPayload) -> Result<Property, String> {
         payload.valuation,
         payload.status,
     );
-    PROPERTIES_STORAGE.with(|storage| storage.borrow_mut().insert(property.id, property.clone()));
+    repo.insert(property.id, property.clone());
     Ok(property)
 }
 
@@ -191,14 +715,17 @@ fn create_property(payload: PropertyPayload) -> Result<Property, String> {
 /// * `Result<Vec<Property>, String>` - A vector of properties or an error message.
 #[ic_cdk::query]
 fn get_all_properties() -> Result<Vec<Property>, String> {
-    PROPERTIES_STORAGE.with(|storage| {
-        let properties = storage.borrow().iter().map(|(_, property)| property.clone()).collect::<Vec<_>>();
-        if properties.is_empty() {
-            Err("No properties found.".to_string())
-        } else {
-            Ok(properties)
-        }
-    })
+    PROPERTIES_STORAGE.with(|storage| get_all_in(&*storage.borrow(), "No properties found."))
+}
+
+/// Collects every value in the repository, erroring when the store is empty.
+fn get_all_in<V, R: Repository<u64, V>>(repo: &R, empty_msg: &str) -> Result<Vec<V>, String> {
+    let values = repo.iter().into_iter().map(|(_, v)| v).collect::<Vec<_>>();
+    if values.is_empty() {
+        Err(empty_msg.to_string())
+    } else {
+        Ok(values)
+    }
 }
 
 /// Validates the lease agreement payload.
@@ -230,21 +757,52 @@ fn validate_lease_agreement_payload(payload: &LeaseAgreementPayload) -> Result<(
 /// 
 /// * `Result<LeaseAgreement, String>` - The created lease agreement or an error message.
 #[ic_cdk::update]
-fn create_lease_agreement(payload: LeaseAgreementPayload) -> Result<LeaseAgreement, String> {
+fn create_lease_agreement(payload: LeaseAgreementPayload) -> Result<LeaseAgreement, Error> {
     // Validate the payload to ensure all fields are provided
-    validate_lease_agreement_payload(&payload)?;
+    validate_lease_agreement_payload(&payload).map_err(|msg| Error::NotFound { msg })?;
     // Validate the property ID
     if !PROPERTIES_STORAGE.with(|storage| storage.borrow().contains_key(&payload.property_id)) {
-        return Err("Property not found".to_string());
+        return Err(Error::NotFound {
+            msg: "Property not found".to_string(),
+        });
     }
-    let id = ID_COUNTER.with(|counter| {
-        let current_value = *counter.borrow().get();
-        counter
-            .borrow_mut()
-            .set(current_value + 1)
-            .expect("Failed to increment ID counter");
-        current_value
-    });
+    // A property can only back one active lease at a time, otherwise `expire_lease`
+    // would free it out from under a tenant whose lease is still running.
+    if property_has_active_lease(payload.property_id) {
+        return Err(Error::Conflict {
+            msg: "Property already has an active lease".to_string(),
+        });
+    }
+    // Reject forged agreements before anything is persisted.
+    verify_lease_payload(&payload)?;
+    let id = next_id();
+    let lease =
+        LEASES_STORAGE.with(|storage| create_lease_agreement_in(&mut *storage.borrow_mut(), id, payload));
+    PROPERTY_LEASE_INDEX
+        .with(|index| index_append(&mut *index.borrow_mut(), lease.property_id, lease.id));
+    append_operation(Operation::CreateLease(lease.clone()));
+    Ok(lease)
+}
+
+/// Checks whether a property currently backs a lease that has not expired or
+/// otherwise ended, used to reject overlapping leases at creation time.
+fn property_has_active_lease(property_id: u64) -> bool {
+    let ids = PROPERTY_LEASE_INDEX
+        .with(|index| posting_page(&*index.borrow(), property_id, None, u32::MAX));
+    LEASES_STORAGE.with(|storage| {
+        let storage = storage.borrow();
+        ids.iter()
+            .filter_map(|id| storage.get(id))
+            .any(|lease| lease.status == "active")
+    })
+}
+
+/// Core lease-creation logic, parametrized over the repository.
+fn create_lease_agreement_in<R: Repository<u64, LeaseAgreement>>(
+    repo: &mut R,
+    id: u64,
+    payload: LeaseAgreementPayload,
+) -> LeaseAgreement {
     let lease = LeaseAgreement::new(
         id,
         payload.property_id,
@@ -253,9 +811,88 @@ fn create_lease_agreement(payload: LeaseAgreementPayload) -> Result<LeaseAgreeme
         payload.start_date,
         payload.end_date,
         payload.digital_signature, // Include digital signature
+        payload.public_key,        // Include the verified public key
     );
-    LEASES_STORAGE.with(|storage| storage.borrow_mut().insert(lease.id, lease.clone()));
-    Ok(lease)
+    repo.insert(lease.id, lease.clone());
+    lease
+}
+
+/// Canonical bytes signed by the tenant: the immutable lease terms, in a fixed
+/// order. Verification and re-verification both sign over exactly these bytes.
+fn canonical_lease_message(
+    property_id: u64,
+    tenant: &str,
+    rent: f64,
+    start_date: u64,
+    end_date: u64,
+) -> Vec<u8> {
+    Encode!(&property_id, &tenant.to_string(), &rent, &start_date, &end_date)
+        .expect("Encoding failed")
+}
+
+/// Decodes a lowercase/uppercase hex string into bytes.
+fn decode_hex(input: &str) -> Result<Vec<u8>, Error> {
+    // Every byte must be an ASCII hex digit before we slice by byte offset
+    // below: an even *byte* length alone doesn't guarantee the offsets land on
+    // `char` boundaries once non-ASCII characters are in the mix, and slicing
+    // off a boundary panics the whole update call.
+    if input.len() % 2 != 0 || !input.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(Error::UnAuthorized {
+            msg: "Signature is not valid hex".to_string(),
+        });
+    }
+    (0..input.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&input[i..i + 2], 16).map_err(|_| Error::UnAuthorized {
+                msg: "Signature is not valid hex".to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Verifies an Ed25519 signature over the canonical lease terms using the
+/// supplied public key, returning `Error::UnAuthorized` on any mismatch.
+fn verify_signature(
+    public_key: &[u8],
+    signature_hex: &str,
+    message: &[u8],
+) -> Result<(), Error> {
+    let key_bytes: [u8; 32] = public_key.try_into().map_err(|_| Error::UnAuthorized {
+        msg: "Public key must be 32 bytes".to_string(),
+    })?;
+    let verifying_key = VerifyingKey::from_bytes(&key_bytes).map_err(|_| Error::UnAuthorized {
+        msg: "Invalid public key".to_string(),
+    })?;
+    let sig_bytes: [u8; 64] = decode_hex(signature_hex)?
+        .try_into()
+        .map_err(|_| Error::UnAuthorized {
+            msg: "Signature must be 64 bytes".to_string(),
+        })?;
+    let signature = Signature::from_bytes(&sig_bytes);
+    verifying_key
+        .verify(message, &signature)
+        .map_err(|_| Error::UnAuthorized {
+            msg: "Signature verification failed".to_string(),
+        })
+}
+
+/// Checks that the payload's signed bytes match the canonical lease terms and
+/// that the Ed25519 signature over them verifies under the tenant's key.
+fn verify_lease_payload(payload: &LeaseAgreementPayload) -> Result<(), Error> {
+    let canonical = canonical_lease_message(
+        payload.property_id,
+        &payload.tenant,
+        payload.rent,
+        payload.start_date,
+        payload.end_date,
+    );
+    if payload.message != canonical {
+        return Err(Error::UnAuthorized {
+            msg: "Signed message does not match the lease terms".to_string(),
+        });
+    }
+    verify_signature(&payload.public_key, &payload.digital_signature, &payload.message)
 }
 
 /// Retrieves all lease agreements from the stable storage.
@@ -265,18 +902,26 @@ fn create_lease_agreement(payload: LeaseAgreementPayload) -> Result<LeaseAgreeme
 /// * `Result<Vec<LeaseAgreement>, String>` - A vector of lease agreements or an error message.
 #[ic_cdk::query]
 fn get_all_lease_agreements() -> Result<Vec<LeaseAgreement>, String> {
-    LEASES_STORAGE.with(|storage| {
-        let leases = storage
-            .borrow()
-            .iter()
-            .map(|(_, lease)| lease.clone())
-            .collect::<Vec<_>>();
-        if leases.is_empty() {
-            Err("No lease agreements found.".to_string())
-        } else {
-            Ok(leases)
-        }
-    })
+    LEASES_STORAGE.with(|storage| get_all_in(&*storage.borrow(), "No lease agreements found."))
+}
+
+/// Re-checks a stored lease's signature on demand, recomputing the canonical
+/// terms from the persisted fields and verifying against the stored public key.
+#[ic_cdk::query]
+fn verify_lease(id: u64) -> Result<(), Error> {
+    let lease = LEASES_STORAGE
+        .with(|storage| storage.borrow().get(&id))
+        .ok_or(Error::NotFound {
+            msg: "Lease agreement not found".to_string(),
+        })?;
+    let canonical = canonical_lease_message(
+        lease.property_id,
+        &lease.tenant,
+        lease.rent,
+        lease.start_date,
+        lease.end_date,
+    );
+    verify_signature(&lease.public_key, &lease.digital_signature, &canonical)
 }
 
 /// Validates the maintenance request payload.
@@ -314,18 +959,62 @@ fn create_maintenance_request(
     if !PROPERTIES_STORAGE.with(|storage| storage.borrow().contains_key(&payload.property_id)) {
         return Err("Property not found".to_string());
     }
-    // Create the maintenance request
-    let id = ID_COUNTER.with(|counter| {
-        let current_value = *counter.borrow().get();
-        counter
-            .borrow_mut()
-            .set(current_value + 1)
-            .expect("Failed to increment ID counter");
-        current_value
+    let id = next_id();
+    let request = MAINTENANCE_STORAGE
+        .with(|storage| create_maintenance_request_in(&mut *storage.borrow_mut(), id, payload))?;
+    MAINTENANCE_STATUS_INDEX
+        .with(|index| index_append(&mut *index.borrow_mut(), request.status.clone(), request.id));
+    append_operation(Operation::CreateMaintenance(request.clone()));
+    Ok(request)
+}
+
+/// Updates a maintenance request's status, keeping the status index in sync and
+/// recording the transition as an operation.
+#[ic_cdk::update]
+fn update_maintenance_status(id: u64, status: String) -> Result<MaintenanceRequest, String> {
+    if status != "pending" && status != "completed" {
+        return Err("Invalid status. Status must be either 'pending' or 'completed'".to_string());
+    }
+    let previous_status = MAINTENANCE_STORAGE.with(|storage| {
+        storage.borrow().get(&id).map(|request| request.status)
     });
-    let request =
-        MaintenanceRequest::new(id, payload.property_id, payload.description, payload.status, payload.priority); // Include priority
-    MAINTENANCE_STORAGE.with(|storage| storage.borrow_mut().insert(request.id, request.clone()));
+    let previous_status = match previous_status {
+        Some(status) => status,
+        None => return Err("Maintenance request not found".to_string()),
+    };
+    let request = MAINTENANCE_STORAGE.with(|storage| {
+        let mut storage = storage.borrow_mut();
+        let mut request = storage.get(&id).expect("request presence checked above");
+        request.status = status.clone();
+        storage.insert(id, request.clone());
+        request
+    });
+    // Move the id between status posting lists.
+    MAINTENANCE_STATUS_INDEX
+        .with(|index| index_remove(&mut *index.borrow_mut(), previous_status, id));
+    MAINTENANCE_STATUS_INDEX
+        .with(|index| index_append(&mut *index.borrow_mut(), status.clone(), id));
+    append_operation(Operation::ChangeMaintenanceStatus {
+        request_id: id,
+        status,
+    });
+    Ok(request)
+}
+
+/// Core maintenance-request-creation logic, parametrized over the repository.
+fn create_maintenance_request_in<R: Repository<u64, MaintenanceRequest>>(
+    repo: &mut R,
+    id: u64,
+    payload: MaintenanceRequestPayload,
+) -> Result<MaintenanceRequest, String> {
+    let request = MaintenanceRequest::new(
+        id,
+        payload.property_id,
+        payload.description,
+        payload.status,
+        payload.priority,
+    ); // Include priority
+    repo.insert(request.id, request.clone());
     Ok(request)
 }
 
@@ -336,29 +1025,172 @@ fn create_maintenance_request(
 /// * `Result<Vec<MaintenanceRequest>, String>` - A vector of maintenance requests or an error message.
 #[ic_cdk::query]
 fn get_all_maintenance_requests() -> Result<Vec<MaintenanceRequest>, String> {
+    MAINTENANCE_STORAGE
+        .with(|storage| get_all_in(&*storage.borrow(), "No maintenance requests found."))
+}
+
+/// Retrieves a page of properties belonging to `owner`, using the owner index.
+///
+/// `start_after` is an exclusive cursor over property ids and `limit` bounds
+/// the response size, so clients page through large portfolios without
+/// scanning the whole table. An empty result just means this page (or this
+/// owner) has no matches — same as `list_documents` for the blob store, there
+/// is nothing exceptional about running out of pages to distinguish with an
+/// error.
+#[ic_cdk::query]
+fn get_properties_by_owner(owner: String, start_after: Option<u64>, limit: u32) -> Vec<Property> {
+    let page =
+        OWNER_PROPERTY_INDEX.with(|index| posting_page(&*index.borrow(), owner, start_after, limit));
+    PROPERTIES_STORAGE.with(|storage| {
+        let storage = storage.borrow();
+        page.iter().filter_map(|id| storage.get(id)).collect::<Vec<_>>()
+    })
+}
+
+/// Retrieves a page of lease agreements for a property, using the lease
+/// index. An empty page (end of pagination, or no leases at all) is not an
+/// error — see `get_properties_by_owner`.
+#[ic_cdk::query]
+fn get_leases_for_property(
+    property_id: u64,
+    start_after: Option<u64>,
+    limit: u32,
+) -> Vec<LeaseAgreement> {
+    let page = PROPERTY_LEASE_INDEX
+        .with(|index| posting_page(&*index.borrow(), property_id, start_after, limit));
+    LEASES_STORAGE.with(|storage| {
+        let storage = storage.borrow();
+        page.iter().filter_map(|id| storage.get(id)).collect::<Vec<_>>()
+    })
+}
+
+/// Retrieves a page of maintenance requests in a given status, using the
+/// status index. An empty page (end of pagination, or no requests in this
+/// status) is not an error — see `get_properties_by_owner`.
+#[ic_cdk::query]
+fn get_maintenance_by_status(
+    status: String,
+    start_after: Option<u64>,
+    limit: u32,
+) -> Vec<MaintenanceRequest> {
+    let page = MAINTENANCE_STATUS_INDEX
+        .with(|index| posting_page(&*index.borrow(), status, start_after, limit));
     MAINTENANCE_STORAGE.with(|storage| {
-        let requests = storage
-            .borrow()
+        let storage = storage.borrow();
+        page.iter().filter_map(|id| storage.get(id)).collect::<Vec<_>>()
+    })
+}
+
+/// Returns the ordered history of operations that touched a given property.
+///
+/// Each entry pairs the operation's timestamp with the operation itself. The
+/// operation log is append-only and never pruned, so this is the property's
+/// complete history back to its creation, regardless of how many checkpoints
+/// have been taken since.
+#[ic_cdk::query]
+fn get_property_history(id: u64) -> Vec<(u64, Operation)> {
+    OPERATION_LOG.with(|log| {
+        log.borrow()
             .iter()
-            .map(|(_, request)| request.clone())
-            .collect::<Vec<_>>();
-        if requests.is_empty() {
-            Err("No maintenance requests found.".to_string())
-        } else {
-            Ok(requests)
-        }
+            .filter(|(_, op)| op.property_id() == Some(id))
+            .collect::<Vec<_>>()
     })
 }
 
+/// Reconstructs the full materialized state as it stood at `timestamp`.
+#[ic_cdk::query]
+fn get_state_at(timestamp: u64) -> StateSnapshot {
+    reconstruct_state(timestamp)
+}
+
 // Implement Storable and BoundedStorable for Data Structures
 
+/// Current on-disk schema version for the record types.
+///
+/// Every record is encoded with this tag as a little-endian `u16` prefix so a
+/// future field addition can be decoded against the version it was written
+/// with, filling defaults for fields that did not yet exist. Records written
+/// before versioning carry no prefix and are treated as version 0.
+const SCHEMA_VERSION: u16 = 3;
+
+/// Encodes a record as `[version: u16 LE][candid bytes]`.
+fn encode_versioned<T: candid::CandidType>(value: &T) -> Vec<u8> {
+    let mut bytes = SCHEMA_VERSION.to_le_bytes().to_vec();
+    bytes.extend(Encode!(value).expect("Encoding failed"));
+    bytes
+}
+
+/// Splits a versioned buffer into its `(version, payload)` parts, returning
+/// version 0 and the whole buffer for unversioned legacy records.
+fn split_version(bytes: &[u8]) -> (u16, &[u8]) {
+    if bytes.len() >= 2 {
+        let version = u16::from_le_bytes([bytes[0], bytes[1]]);
+        if (1..=SCHEMA_VERSION).contains(&version) {
+            return (version, &bytes[2..]);
+        }
+    }
+    (0, bytes)
+}
+
+// Version 0 (pre-versioning) layouts, kept only to migrate older records. Fields
+// that unversioned records may or may not carry are optional here, so both
+// decode and a present value is kept.
+#[derive(candid::CandidType, Deserialize)]
+struct LeaseAgreementV0 {
+    id: u64,
+    property_id: u64,
+    tenant: String,
+    rent: f64,
+    start_date: u64,
+    end_date: u64,
+    created_at: u64,
+    digital_signature: Option<String>,
+}
+
+#[derive(candid::CandidType, Deserialize)]
+struct LeaseAgreementV1 {
+    id: u64,
+    property_id: u64,
+    tenant: String,
+    rent: f64,
+    start_date: u64,
+    end_date: u64,
+    created_at: u64,
+    digital_signature: String,
+}
+
+#[derive(candid::CandidType, Deserialize)]
+struct LeaseAgreementV2 {
+    id: u64,
+    property_id: u64,
+    tenant: String,
+    rent: f64,
+    start_date: u64,
+    end_date: u64,
+    created_at: u64,
+    digital_signature: String,
+    public_key: Vec<u8>,
+}
+
+#[derive(candid::CandidType, Deserialize)]
+struct MaintenanceRequestV0 {
+    id: u64,
+    property_id: u64,
+    description: String,
+    status: String,
+    created_at: u64,
+    priority: Option<String>,
+}
+
 impl Storable for Property {
     fn to_bytes(&self) -> Cow<[u8]> {
-        Cow::Owned(Encode!(self).expect("Encoding failed"))
+        Cow::Owned(encode_versioned(self))
     }
 
     fn from_bytes(bytes: Cow<[u8]>) -> Self {
-        Decode!(bytes.as_ref(), Self).expect("Decoding failed")
+        // `Property` has not gained fields, so every version shares a layout.
+        let (_, payload) = split_version(bytes.as_ref());
+        Decode!(payload, Self).expect("Decoding failed")
     }
 }
 
@@ -369,11 +1201,62 @@ impl BoundedStorable for Property {
 
 impl Storable for LeaseAgreement {
     fn to_bytes(&self) -> Cow<[u8]> {
-        Cow::Owned(Encode!(self).expect("Encoding failed"))
+        Cow::Owned(encode_versioned(self))
     }
 
     fn from_bytes(bytes: Cow<[u8]>) -> Self {
-        Decode!(bytes.as_ref(), Self).expect("Decoding failed")
+        let (version, payload) = split_version(bytes.as_ref());
+        match version {
+            // Version 0 may lack `digital_signature`, and lacks `public_key` and `status`.
+            0 => {
+                let old = Decode!(payload, LeaseAgreementV0).expect("Decoding failed");
+                LeaseAgreement {
+                    id: old.id,
+                    property_id: old.property_id,
+                    tenant: old.tenant,
+                    rent: old.rent,
+                    start_date: old.start_date,
+                    end_date: old.end_date,
+                    created_at: old.created_at,
+                    digital_signature: old.digital_signature.unwrap_or_default(),
+                    public_key: Vec::new(),
+                    status: "active".to_string(),
+                }
+            }
+            // Version 1 carried `digital_signature` but no `public_key` or `status`.
+            1 => {
+                let old = Decode!(payload, LeaseAgreementV1).expect("Decoding failed");
+                LeaseAgreement {
+                    id: old.id,
+                    property_id: old.property_id,
+                    tenant: old.tenant,
+                    rent: old.rent,
+                    start_date: old.start_date,
+                    end_date: old.end_date,
+                    created_at: old.created_at,
+                    digital_signature: old.digital_signature,
+                    public_key: Vec::new(),
+                    status: "active".to_string(),
+                }
+            }
+            // Version 2 added the verified `public_key` but predates `status`.
+            2 => {
+                let old = Decode!(payload, LeaseAgreementV2).expect("Decoding failed");
+                LeaseAgreement {
+                    id: old.id,
+                    property_id: old.property_id,
+                    tenant: old.tenant,
+                    rent: old.rent,
+                    start_date: old.start_date,
+                    end_date: old.end_date,
+                    created_at: old.created_at,
+                    digital_signature: old.digital_signature,
+                    public_key: old.public_key,
+                    status: "active".to_string(),
+                }
+            }
+            _ => Decode!(payload, Self).expect("Decoding failed"),
+        }
     }
 }
 
@@ -384,11 +1267,24 @@ impl BoundedStorable for LeaseAgreement {
 
 impl Storable for MaintenanceRequest {
     fn to_bytes(&self) -> Cow<[u8]> {
-        Cow::Owned(Encode!(self).expect("Encoding failed"))
+        Cow::Owned(encode_versioned(self))
     }
 
     fn from_bytes(bytes: Cow<[u8]>) -> Self {
-        Decode!(bytes.as_ref(), Self).expect("Decoding failed")
+        let (version, payload) = split_version(bytes.as_ref());
+        if version == 0 {
+            // Unversioned records may lack `priority`; default it when absent.
+            let old = Decode!(payload, MaintenanceRequestV0).expect("Decoding failed");
+            return MaintenanceRequest {
+                id: old.id,
+                property_id: old.property_id,
+                description: old.description,
+                status: old.status,
+                created_at: old.created_at,
+                priority: old.priority.unwrap_or_default(),
+            };
+        }
+        Decode!(payload, Self).expect("Decoding failed")
     }
 }
 
@@ -397,14 +1293,677 @@ impl BoundedStorable for MaintenanceRequest {
     const IS_FIXED_SIZE: bool = false;
 }
 
+impl Storable for Operation {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(encode_versioned(self))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        // `Operation` has not gained variants, so every version shares a layout.
+        let (_, payload) = split_version(bytes.as_ref());
+        Decode!(payload, Self).expect("Decoding failed")
+    }
+}
+
+impl BoundedStorable for Operation {
+    // A single operation carries at most one materialized record plus framing.
+    const MAX_SIZE: u32 = 2048;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+impl Storable for StateSnapshot {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(encode_versioned(self))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        // `StateSnapshot` has not gained fields, so every version shares a layout.
+        let (_, payload) = split_version(bytes.as_ref());
+        Decode!(payload, Self).expect("Decoding failed")
+    }
+}
+
+impl Storable for BlobRef {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("Encoding failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).expect("Decoding failed")
+    }
+}
+
+impl BoundedStorable for BlobRef {
+    // Two short identifiers plus the owning id and candid framing.
+    const MAX_SIZE: u32 = 512;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+impl Storable for BlobChunkKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("Encoding failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).expect("Decoding failed")
+    }
+}
+
+impl BoundedStorable for BlobChunkKey {
+    // A `BlobRef` plus the chunk index.
+    const MAX_SIZE: u32 = 512;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+impl Storable for BlobMeta {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("Encoding failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).expect("Decoding failed")
+    }
+}
+
+impl BoundedStorable for BlobMeta {
+    // A chunk count plus a 32-byte digest and candid framing.
+    const MAX_SIZE: u32 = 128;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+impl Storable for Chunk {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("Encoding failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).expect("Decoding failed")
+    }
+}
+
+impl BoundedStorable for Chunk {
+    // One full chunk of bytes plus candid framing.
+    const MAX_SIZE: u32 = CHUNK_SIZE as u32 + 1024;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+// Blob Store Handlers
+
+/// Writes one chunk of a document at the given byte `offset`.
+///
+/// The offset must be chunk-aligned; the chunk lands at `offset / CHUNK_SIZE`
+/// and must not exceed `CHUNK_SIZE` bytes. Uploading reopens a document, so the
+/// `finalized` flag is cleared until `finalize_document` runs again.
+#[ic_cdk::update]
+fn upload_document_chunk(reference: BlobRef, offset: u64, bytes: Vec<u8>) -> Result<(), Error> {
+    if offset % CHUNK_SIZE != 0 {
+        return Err(Error::NotFound {
+            msg: "Offset must be a multiple of the chunk size".to_string(),
+        });
+    }
+    if bytes.len() as u64 > CHUNK_SIZE {
+        return Err(Error::NotFound {
+            msg: "Chunk exceeds the maximum chunk size".to_string(),
+        });
+    }
+    let index = (offset / CHUNK_SIZE) as u32;
+    // A new upload sequence starts at offset 0; clear any chunks left over
+    // from a previous, possibly longer, sequence so a shorter re-upload can't
+    // leave stale tail chunks beyond the new `chunk_count`.
+    if index == 0 {
+        let old_chunk_count = BLOB_META.with(|meta| {
+            meta.borrow()
+                .get(&reference)
+                .map(|m| m.chunk_count)
+                .unwrap_or(0)
+        });
+        BLOB_CHUNKS.with(|chunks| {
+            let mut chunks = chunks.borrow_mut();
+            for stale_index in 1..old_chunk_count {
+                chunks.remove(&BlobChunkKey {
+                    owner_kind: reference.owner_kind.clone(),
+                    owner_id: reference.owner_id,
+                    key: reference.key.clone(),
+                    index: stale_index,
+                });
+            }
+        });
+    }
+    let chunk_key = BlobChunkKey {
+        owner_kind: reference.owner_kind.clone(),
+        owner_id: reference.owner_id,
+        key: reference.key.clone(),
+        index,
+    };
+    BLOB_CHUNKS.with(|chunks| chunks.borrow_mut().insert(chunk_key, Chunk(bytes)));
+    BLOB_META.with(|meta| {
+        let mut meta_map = meta.borrow_mut();
+        let mut entry = meta_map.get(&reference).unwrap_or_default();
+        entry.chunk_count = if index == 0 {
+            1
+        } else {
+            entry.chunk_count.max(index + 1)
+        };
+        entry.finalized = false;
+        meta_map.insert(reference, entry);
+    });
+    Ok(())
+}
+
+/// Reassembles a document and seals it against the client-supplied SHA-256,
+/// rejecting with `Error::UnAuthorized` if the reassembled bytes do not hash to
+/// the expected value.
+#[ic_cdk::update]
+fn finalize_document(reference: BlobRef, sha256: Vec<u8>) -> Result<(), Error> {
+    let mut entry = BLOB_META
+        .with(|meta| meta.borrow().get(&reference))
+        .ok_or(Error::NotFound {
+            msg: "Document not found".to_string(),
+        })?;
+    let data = reassemble_document(&reference, entry.chunk_count)?;
+    let digest = Sha256::digest(&data);
+    if digest.as_slice() != sha256.as_slice() {
+        return Err(Error::UnAuthorized {
+            msg: "Content hash does not match the uploaded document".to_string(),
+        });
+    }
+    entry.sha256 = sha256;
+    entry.finalized = true;
+    BLOB_META.with(|meta| meta.borrow_mut().insert(reference, entry));
+    Ok(())
+}
+
+/// Returns the reassembled bytes of a finalized document.
+#[ic_cdk::query]
+fn get_document(reference: BlobRef) -> Result<Vec<u8>, Error> {
+    let entry = BLOB_META
+        .with(|meta| meta.borrow().get(&reference))
+        .ok_or(Error::NotFound {
+            msg: "Document not found".to_string(),
+        })?;
+    if !entry.finalized {
+        return Err(Error::NotFound {
+            msg: "Document has not been finalized".to_string(),
+        });
+    }
+    reassemble_document(&reference, entry.chunk_count)
+}
+
+/// Lists the references of every document owned by a given record id.
+#[ic_cdk::query]
+fn list_documents(owner_id: u64) -> Vec<BlobRef> {
+    BLOB_META.with(|meta| {
+        meta.borrow()
+            .iter()
+            .map(|(reference, _)| reference)
+            .filter(|reference| reference.owner_id == owner_id)
+            .collect::<Vec<_>>()
+    })
+}
+
+/// Concatenates a document's chunks in index order, erroring on a missing chunk.
+fn reassemble_document(reference: &BlobRef, chunk_count: u32) -> Result<Vec<u8>, Error> {
+    let mut data = Vec::new();
+    BLOB_CHUNKS.with(|chunks| {
+        let chunks = chunks.borrow();
+        for index in 0..chunk_count {
+            let chunk_key = BlobChunkKey {
+                owner_kind: reference.owner_kind.clone(),
+                owner_id: reference.owner_id,
+                key: reference.key.clone(),
+                index,
+            };
+            match chunks.get(&chunk_key) {
+                Some(chunk) => data.extend(chunk.0),
+                None => {
+                    return Err(Error::NotFound {
+                        msg: format!("Missing chunk {index}"),
+                    })
+                }
+            }
+        }
+        Ok(data)
+    })
+}
+
+// Upgrade Migration
+
+/// Rewrites every stored record after a canister upgrade.
+///
+/// Reading a record decodes it against whatever version it was written with and
+/// upgrades it in memory; re-inserting it persists the current-version
+/// encoding, so the stable maps never hold a record older than the code.
+#[ic_cdk::post_upgrade]
+fn post_upgrade() {
+    PROPERTIES_STORAGE.with(|storage| {
+        let mut storage = storage.borrow_mut();
+        let records = storage.iter().collect::<Vec<_>>();
+        for (id, property) in records {
+            storage.insert(id, property);
+        }
+    });
+    LEASES_STORAGE.with(|storage| {
+        let mut storage = storage.borrow_mut();
+        let records = storage.iter().collect::<Vec<_>>();
+        for (id, lease) in records {
+            storage.insert(id, lease);
+        }
+    });
+    MAINTENANCE_STORAGE.with(|storage| {
+        let mut storage = storage.borrow_mut();
+        let records = storage.iter().collect::<Vec<_>>();
+        for (id, request) in records {
+            storage.insert(id, request);
+        }
+    });
+    OPERATION_LOG.with(|log| {
+        let mut log = log.borrow_mut();
+        let records = log.iter().collect::<Vec<_>>();
+        for (timestamp, op) in records {
+            log.insert(timestamp, op);
+        }
+    });
+    // `Cell::init` already decoded the stored snapshot against its original
+    // version when this thread-local was constructed; re-`set` it so it is
+    // persisted back under the current schema version.
+    CHECKPOINT.with(|cell| {
+        let snapshot = cell.borrow().get().clone();
+        cell.borrow_mut()
+            .set(snapshot)
+            .expect("Failed to migrate checkpoint");
+    });
+    // Timers do not survive an upgrade, so re-arm the lifecycle scan.
+    start_lifecycle_timer();
+}
+
+// Lease Lifecycle
+
+/// Number of seconds in one rent cycle, used to emit recurring rent-due entries.
+const RENT_PERIOD_SECS: u64 = 30 * 24 * 60 * 60; // 30 days
+
+/// Controls how often the lifecycle timer scans and how long after `end_date` a
+/// lease is allowed to run before it is marked expired.
+#[derive(candid::CandidType, Serialize, Deserialize, Clone)]
+struct LifecyclePolicy {
+    scan_interval_secs: u64,
+    grace_period_secs: u64,
+}
+
+impl Default for LifecyclePolicy {
+    fn default() -> Self {
+        Self {
+            scan_interval_secs: 24 * 60 * 60, // Scan once a day by default
+            grace_period_secs: 0,
+        }
+    }
+}
+
+impl Storable for LifecyclePolicy {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).expect("Encoding failed"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).expect("Decoding failed")
+    }
+}
+
+impl BoundedStorable for LifecyclePolicy {
+    // Two `u64`s plus candid framing.
+    const MAX_SIZE: u32 = 64;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+/// Registers the periodic lifecycle timer, cancelling any previously armed one.
+fn start_lifecycle_timer() {
+    let interval = LIFECYCLE_POLICY.with(|policy| policy.borrow().get().scan_interval_secs);
+    LIFECYCLE_TIMER.with(|timer| {
+        if let Some(previous) = timer.borrow_mut().take() {
+            ic_cdk_timers::clear_timer(previous);
+        }
+        let handle = ic_cdk_timers::set_timer_interval(Duration::from_secs(interval), || {
+            run_lifecycle_scan();
+        });
+        *timer.borrow_mut() = Some(handle);
+    });
+}
+
+/// Scans every lease, expiring those past `end_date` + grace, releasing their
+/// property, and emitting a rent-due entry once per elapsed rent cycle.
+///
+/// Each transition is recorded as an operation so the audit history captures the
+/// automatic change just as it would a manual one.
+fn run_lifecycle_scan() {
+    let now = time() / 1_000_000_000; // Convert nanoseconds to seconds
+    let grace = LIFECYCLE_POLICY.with(|policy| policy.borrow().get().grace_period_secs);
+
+    let leases = LEASES_STORAGE
+        .with(|storage| storage.borrow().iter().map(|(_, l)| l).collect::<Vec<_>>());
+    for lease in leases {
+        if lease.status == "active" && now >= lease.end_date.saturating_add(grace) {
+            expire_lease(&lease);
+        } else if lease.status == "active" {
+            emit_due_rent(&lease, now);
+        }
+    }
+}
+
+/// Marks a lease expired and flips its property back to `available`, recording
+/// both transitions in the operation log.
+fn expire_lease(lease: &LeaseAgreement) {
+    // Safe to free the property unconditionally: `create_lease_agreement` rejects
+    // a new lease while this property already has another active one, so at most
+    // one active lease can reference `lease.property_id` at a time.
+    LEASES_STORAGE.with(|storage| {
+        let mut storage = storage.borrow_mut();
+        if let Some(mut stored) = storage.get(&lease.id) {
+            stored.status = "expired".to_string();
+            storage.insert(lease.id, stored);
+        }
+    });
+    append_operation(Operation::ChangeLeaseStatus {
+        lease_id: lease.id,
+        status: "expired".to_string(),
+    });
+
+    PROPERTIES_STORAGE.with(|storage| {
+        let mut storage = storage.borrow_mut();
+        if let Some(mut property) = storage.get(&lease.property_id) {
+            property.status = "available".to_string();
+            storage.insert(lease.property_id, property);
+        }
+    });
+    append_operation(Operation::ChangePropertyStatus {
+        property_id: lease.property_id,
+        status: "available".to_string(),
+    });
+}
+
+/// Emits a rent-due maintenance entry for each rent cycle that has fully elapsed
+/// since the lease started and has not yet been billed.
+fn emit_due_rent(lease: &LeaseAgreement, now: u64) {
+    if now < lease.start_date {
+        return;
+    }
+    let current_cycle = (now - lease.start_date) / RENT_PERIOD_SECS;
+    let last_cycle = LAST_RENT_CYCLE.with(|cycles| cycles.borrow().get(&lease.id).unwrap_or(0));
+    if current_cycle <= last_cycle {
+        return;
+    }
+    for cycle in (last_cycle + 1)..=current_cycle {
+        let id = next_id();
+        let request = MaintenanceRequest::new(
+            id,
+            lease.property_id,
+            format!("Rent due for lease {} (cycle {cycle})", lease.id),
+            "pending".to_string(),
+            "low".to_string(),
+        );
+        MAINTENANCE_STORAGE.with(|storage| storage.borrow_mut().insert(id, request.clone()));
+        MAINTENANCE_STATUS_INDEX
+            .with(|index| index_append(&mut *index.borrow_mut(), request.status.clone(), request.id));
+        append_operation(Operation::CreateMaintenance(request));
+    }
+    LAST_RENT_CYCLE.with(|cycles| cycles.borrow_mut().insert(lease.id, current_cycle));
+}
+
+/// Lower bound on the lifecycle scan interval. Without a floor, a caller could
+/// reconfigure the canister to re-scan every lease on a near-continuous
+/// timer, draining cycles; this keeps the interval sane even if validation
+/// elsewhere is loosened.
+const MIN_SCAN_INTERVAL_SECS: u64 = 60;
+
+/// Reconfigures the lifecycle scan interval and expiry grace period, then
+/// re-arms the timer with the new interval. Restricted to a controller: this
+/// governs how often the canister burns cycles scanning leases, so it is not
+/// safe to leave open to anonymous callers.
+#[ic_cdk::update]
+fn set_lifecycle_policy(scan_interval_secs: u64, grace_period_secs: u64) -> Result<(), Error> {
+    if !ic_cdk::api::is_controller(&ic_cdk::caller()) {
+        return Err(Error::UnAuthorized {
+            msg: "Only a controller can change the lifecycle policy".to_string(),
+        });
+    }
+    if scan_interval_secs < MIN_SCAN_INTERVAL_SECS {
+        return Err(Error::NotFound {
+            msg: format!("Scan interval must be at least {MIN_SCAN_INTERVAL_SECS} seconds"),
+        });
+    }
+    let policy = LifecyclePolicy {
+        scan_interval_secs,
+        grace_period_secs,
+    };
+    LIFECYCLE_POLICY.with(|cell| {
+        cell.borrow_mut()
+            .set(policy)
+            .expect("Failed to write lifecycle policy")
+    });
+    start_lifecycle_timer();
+    Ok(())
+}
+
+/// Arms the lifecycle timer on a fresh install.
+#[ic_cdk::init]
+fn init() {
+    start_lifecycle_timer();
+}
+
 // Error Types
 
 #[derive(candid::CandidType, Deserialize, Serialize)]
 enum Error {
     NotFound { msg: String },
     UnAuthorized { msg: String },
+    Conflict { msg: String },
 }
 
 // Generate Candid
 
-ic_cdk::export_candid!();
\ No newline at end of file
+ic_cdk::export_candid!();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use std::collections::BTreeMap;
+
+    fn hex_encode(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    #[test]
+    fn index_append_keeps_postings_sorted_and_deduplicated() {
+        let mut index: BTreeMap<IndexEntry<String>, Present> = BTreeMap::new();
+        index_append(&mut index, "alice".to_string(), 5);
+        index_append(&mut index, "alice".to_string(), 1);
+        index_append(&mut index, "alice".to_string(), 5); // duplicate, collapses to one entry
+        let postings = Repository::iter(&index)
+            .into_iter()
+            .map(|(entry, _)| entry.id)
+            .collect::<Vec<_>>();
+        assert_eq!(postings, vec![1, 5]);
+    }
+
+    #[test]
+    fn index_remove_drops_only_the_matching_posting() {
+        let mut index: BTreeMap<IndexEntry<String>, Present> = BTreeMap::new();
+        index_append(&mut index, "alice".to_string(), 1);
+        index_append(&mut index, "alice".to_string(), 5);
+        index_append(&mut index, "bob".to_string(), 1);
+        index_remove(&mut index, "alice".to_string(), 1);
+        let postings = Repository::iter(&index)
+            .into_iter()
+            .map(|(entry, _)| (entry.index_key, entry.id))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            postings,
+            vec![("alice".to_string(), 5), ("bob".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn create_property_in_validates_payload_and_inserts() {
+        let mut repo: BTreeMap<u64, Property> = BTreeMap::new();
+        let err = create_property_in(
+            &mut repo,
+            1,
+            PropertyPayload {
+                address: String::new(),
+                owner: "alice".to_string(),
+                valuation: 100.0,
+                status: "available".to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, "Address and owner are required");
+
+        let property = create_property_in(
+            &mut repo,
+            1,
+            PropertyPayload {
+                address: "1 Main St".to_string(),
+                owner: "alice".to_string(),
+                valuation: 100.0,
+                status: "available".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(property.id, 1);
+        assert_eq!(Repository::get(&repo, &1).unwrap().owner, "alice");
+    }
+
+    #[test]
+    fn create_lease_agreement_in_inserts_into_repository() {
+        let mut repo: BTreeMap<u64, LeaseAgreement> = BTreeMap::new();
+        let lease = create_lease_agreement_in(
+            &mut repo,
+            7,
+            LeaseAgreementPayload {
+                property_id: 1,
+                tenant: "bob".to_string(),
+                rent: 1200.0,
+                start_date: 0,
+                end_date: 1,
+                digital_signature: String::new(),
+                public_key: Vec::new(),
+                message: Vec::new(),
+            },
+        );
+        assert_eq!(lease.id, 7);
+        assert_eq!(lease.status, "active");
+        assert_eq!(Repository::get(&repo, &7).unwrap().tenant, "bob");
+    }
+
+    #[test]
+    fn apply_operation_replays_creates_and_mutations_onto_a_snapshot() {
+        let mut state = StateSnapshot::default();
+        // Built directly: `Property::new` reads the canister clock, which is
+        // unavailable in a host test.
+        let property = Property {
+            id: 1,
+            address: "1 Main St".to_string(),
+            owner: "alice".to_string(),
+            valuation: 100.0,
+            status: "available".to_string(),
+            created_at: 0,
+        };
+        apply_operation(&Operation::CreateProperty(property.clone()), &mut state);
+        apply_operation(
+            &Operation::UpdateValuation {
+                property_id: 1,
+                valuation: 150.0,
+            },
+            &mut state,
+        );
+        apply_operation(
+            &Operation::ChangePropertyStatus {
+                property_id: 1,
+                status: "leased".to_string(),
+            },
+            &mut state,
+        );
+
+        assert_eq!(state.properties.len(), 1);
+        assert_eq!(state.properties[0].valuation, 150.0);
+        assert_eq!(state.properties[0].status, "leased");
+    }
+
+    #[test]
+    fn encode_versioned_round_trips_through_split_version() {
+        let property = Property {
+            id: 1,
+            address: "1 Main St".to_string(),
+            owner: "alice".to_string(),
+            valuation: 100.0,
+            status: "available".to_string(),
+            created_at: 0,
+        };
+        let bytes = encode_versioned(&property);
+        let (version, payload) = split_version(&bytes);
+        assert_eq!(version, SCHEMA_VERSION);
+        let decoded = Decode!(payload, Property).unwrap();
+        assert_eq!(decoded.id, property.id);
+        assert_eq!(decoded.address, property.address);
+    }
+
+    #[test]
+    fn split_version_treats_legacy_unversioned_bytes_as_version_zero() {
+        let legacy = Encode!(&MaintenanceRequestV0 {
+            id: 1,
+            property_id: 2,
+            description: "leaky faucet".to_string(),
+            status: "pending".to_string(),
+            created_at: 0,
+            priority: None,
+        })
+        .unwrap();
+        let (version, payload) = split_version(&legacy);
+        assert_eq!(version, 0);
+        assert_eq!(payload, legacy.as_slice());
+    }
+
+    #[test]
+    fn operation_and_state_snapshot_storable_round_trip_through_versioned_encoding() {
+        let op = Operation::ChangePropertyStatus {
+            property_id: 1,
+            status: "leased".to_string(),
+        };
+        let bytes = op.to_bytes();
+        let (version, _) = split_version(bytes.as_ref());
+        assert_eq!(version, SCHEMA_VERSION);
+        assert!(matches!(
+            Operation::from_bytes(bytes),
+            Operation::ChangePropertyStatus { property_id: 1, status } if status == "leased"
+        ));
+
+        let snapshot = StateSnapshot {
+            last_timestamp: 42,
+            ..StateSnapshot::default()
+        };
+        let bytes = snapshot.to_bytes();
+        let (version, _) = split_version(bytes.as_ref());
+        assert_eq!(version, SCHEMA_VERSION);
+        assert_eq!(StateSnapshot::from_bytes(bytes).last_timestamp, 42);
+    }
+
+    #[test]
+    fn decode_hex_round_trips_and_rejects_invalid_input() {
+        assert_eq!(decode_hex("0aFF").unwrap(), vec![0x0a, 0xff]);
+        assert!(decode_hex("abc").is_err()); // odd length
+        assert!(decode_hex("zz").is_err()); // non-hex ascii
+        assert!(decode_hex("1éb").is_err()); // even byte length, non-ASCII char
+    }
+
+    #[test]
+    fn verify_signature_accepts_genuine_and_rejects_tampered() {
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let verifying_key = signing_key.verifying_key();
+        let message = canonical_lease_message(1, "bob", 1200.0, 0, 1);
+        let signature_hex = hex_encode(&signing_key.sign(&message).to_bytes());
+
+        assert!(verify_signature(verifying_key.as_bytes(), &signature_hex, &message).is_ok());
+
+        let tampered = canonical_lease_message(1, "bob", 1300.0, 0, 1);
+        assert!(verify_signature(verifying_key.as_bytes(), &signature_hex, &tampered).is_err());
+    }
+}
\ No newline at end of file